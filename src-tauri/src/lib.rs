@@ -1,20 +1,157 @@
+use std::net::TcpListener;
 use std::process::{Child, Command};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
-use tauri::{Manager, State};
+use std::time::{Duration, Instant};
+use tauri::http::{Request, Response};
+use tauri::{AppHandle, Emitter, Manager, RunEvent, State, UriSchemeResponder};
 
-struct BackendProcess(Mutex<Option<Child>>);
+struct BackendProcess {
+    child: Mutex<Option<Child>>,
+    /// Set when we are deliberately tearing the backend down (app exit or a
+    /// manual `stop_backend`) so the supervisor doesn't try to "rescue" the kill.
+    shutting_down: AtomicBool,
+    /// Ephemeral port the backend was bound to for this run, so a second
+    /// instance or any other local service doesn't clash on a fixed port.
+    port: u16,
+    /// `http://127.0.0.1:<port>` base URL the frontend should talk to.
+    base_url: String,
+    /// When the current child was spawned, used to report uptime.
+    started_at: Mutex<Option<Instant>>,
+}
+
+impl BackendProcess {
+    fn new(port: u16) -> Self {
+        BackendProcess {
+            child: Mutex::new(None),
+            shutting_down: AtomicBool::new(false),
+            port,
+            base_url: format!("http://127.0.0.1:{}", port),
+            started_at: Mutex::new(None),
+        }
+    }
+}
+
+/// Serializable snapshot of the backend for the frontend / dev tooling.
+#[derive(serde::Serialize)]
+struct BackendStatus {
+    running: bool,
+    pid: Option<u32>,
+    uptime_secs: Option<u64>,
+}
+
+/// Grab a free ephemeral port from the OS by binding to port 0 and reading back
+/// the assigned port. The listener is dropped immediately so uvicorn can claim
+/// it; the small race window is acceptable for a single desktop launch.
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .and_then(|l| l.local_addr())
+        .map(|addr| addr.port())
+        .unwrap_or(8000)
+}
+
+/// How long to wait for the backend to start accepting connections before
+/// giving up and emitting `backend-error`.
+const READINESS_TIMEOUT: Duration = Duration::from_secs(30);
+/// Interval between readiness probes.
+const READINESS_INTERVAL: Duration = Duration::from_millis(200);
+/// How often the supervisor checks whether the backend is still alive.
+const SUPERVISOR_INTERVAL: Duration = Duration::from_secs(1);
+/// Give up respawning after this many consecutive failed restarts.
+const MAX_RESTARTS: u32 = 5;
+/// Cap for the exponential restart backoff.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// How long to let the backend shut down gracefully before hard-killing it.
+const GRACEFUL_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[tauri::command]
 fn greet(name: &str) -> String {
     format!("Hello, {}! Welcome to Lazy Learn.", name)
 }
 
-fn spawn_backend() -> Option<Child> {
+/// Base URL the webview should use instead of assuming `:8000`.
+#[tauri::command]
+fn backend_url(state: State<BackendProcess>) -> String {
+    state.base_url.clone()
+}
+
+/// Kill the current backend and spawn a fresh one, returning the new PID. Lets
+/// the UI offer a "restart server" button when the backend gets wedged.
+#[tauri::command]
+fn restart_backend(app: AppHandle, state: State<BackendProcess>) -> Result<u32, String> {
+    // Re-arm supervision: a prior `stop_backend` (or a give-up after the
+    // restart ceiling) parked the supervisor via `shutting_down`.
+    state.shutting_down.store(false, Ordering::SeqCst);
+    // Swap the child under the lock so the supervisor never observes the gap
+    // and races us with a restart of its own.
+    let mut guard = state.child.lock().unwrap();
+    if let Some(mut child) = guard.take() {
+        terminate_backend(&mut child);
+    }
+    match spawn_backend(state.port) {
+        Some(child) => {
+            let pid = child.id();
+            *guard = Some(child);
+            *state.started_at.lock().unwrap() = Some(Instant::now());
+            drop(guard);
+            probe_backend_ready(app);
+            Ok(pid)
+        }
+        None => Err("failed to spawn backend".into()),
+    }
+}
+
+/// Stop the backend without respawning it; flags shutdown so the supervisor
+/// leaves it down until the next deliberate restart.
+#[tauri::command]
+fn stop_backend(state: State<BackendProcess>) -> Result<(), String> {
+    state.shutting_down.store(true, Ordering::SeqCst);
+    if let Some(mut child) = state.child.lock().unwrap().take() {
+        terminate_backend(&mut child);
+    }
+    *state.started_at.lock().unwrap() = None;
+    Ok(())
+}
+
+/// Report whether the backend is running, plus its PID and uptime.
+#[tauri::command]
+fn get_backend_status(state: State<BackendProcess>) -> BackendStatus {
+    let mut guard = state.child.lock().unwrap();
+    let running = match guard.as_mut() {
+        Some(child) => matches!(child.try_wait(), Ok(None)),
+        None => false,
+    };
+    let pid = if running { guard.as_ref().map(|c| c.id()) } else { None };
+    let uptime_secs = if running {
+        state
+            .started_at
+            .lock()
+            .unwrap()
+            .map(|t| t.elapsed().as_secs())
+    } else {
+        None
+    };
+    BackendStatus {
+        running,
+        pid,
+        uptime_secs,
+    }
+}
+
+fn spawn_backend(port: u16) -> Option<Child> {
     // Try to spawn the Python backend
-    // In development: uvicorn app.main:app --port 8000
+    // In development: uvicorn app.main:app --port <ephemeral>
     // In production: bundled executable (deferred to Task 28)
     let result = Command::new("python")
-        .args(["-m", "uvicorn", "app.main:app", "--port", "8000", "--host", "127.0.0.1"])
+        .args([
+            "-m",
+            "uvicorn",
+            "app.main:app",
+            "--port",
+            &port.to_string(),
+            "--host",
+            "127.0.0.1",
+        ])
         .current_dir("../backend")
         .spawn();
 
@@ -30,29 +167,298 @@ fn spawn_backend() -> Option<Child> {
     }
 }
 
+/// Poll the backend's `/health` endpoint until it answers with a 2xx, then tell
+/// the frontend it can connect. The webview listens for `backend-ready` instead
+/// of racing the connection, which removes startup flicker and lets the UI show
+/// a proper loading state.
+fn probe_backend_ready(app: AppHandle) {
+    std::thread::spawn(move || {
+        let health_url = format!("{}/health", app.state::<BackendProcess>().base_url);
+        let client = reqwest::blocking::Client::new();
+        let deadline = Instant::now() + READINESS_TIMEOUT;
+        loop {
+            if let Ok(resp) = client
+                .get(&health_url)
+                .timeout(READINESS_INTERVAL)
+                .send()
+            {
+                if resp.status().is_success() {
+                    let _ = app.emit("backend-ready", ());
+                    return;
+                }
+            }
+            if Instant::now() >= deadline {
+                let _ = app.emit(
+                    "backend-error",
+                    format!(
+                        "backend did not become ready within {}s",
+                        READINESS_TIMEOUT.as_secs()
+                    ),
+                );
+                return;
+            }
+            std::thread::sleep(READINESS_INTERVAL);
+        }
+    });
+}
+
+/// Watch the backend child and bring it back if it dies unexpectedly. Uses
+/// exponential backoff so a backend that keeps crashing doesn't get hammered,
+/// and stops after `MAX_RESTARTS` consecutive failures. The `shutting_down`
+/// flag parks the supervisor (deliberate kill on app exit, or a manual
+/// `stop_backend`) without tearing the thread down, so a later `restart_backend`
+/// that clears the flag re-arms supervision for the rest of the session.
+fn supervise_backend(app: AppHandle) {
+    std::thread::spawn(move || {
+        let state: State<BackendProcess> = app.state();
+        let mut restarts: u32 = 0;
+        loop {
+            std::thread::sleep(SUPERVISOR_INTERVAL);
+            if state.shutting_down.load(Ordering::SeqCst) {
+                restarts = 0;
+                continue;
+            }
+
+            // Is the backend down while the window is still open? Reap the dead
+            // handle under the lock so it can't be orphaned, and so a concurrent
+            // `restart_backend` can install a fresh child into the empty slot. A
+            // `None` child means a prior respawn failed, which counts as "down"
+            // too — otherwise a transiently-unavailable uvicorn would idle us.
+            let down = {
+                let mut guard = state.child.lock().unwrap();
+                match guard.as_mut() {
+                    Some(child) => match child.try_wait() {
+                        Ok(Some(_)) | Err(_) => {
+                            guard.take();
+                            true
+                        }
+                        Ok(None) => false,
+                    },
+                    None => true,
+                }
+            };
+            if !down {
+                restarts = 0;
+                continue;
+            }
+
+            if state.shutting_down.load(Ordering::SeqCst) {
+                continue;
+            }
+            if restarts >= MAX_RESTARTS {
+                eprintln!("Backend crashed and exceeded {} restarts; giving up.", MAX_RESTARTS);
+                let _ = app.emit("backend-crashed", ());
+                // Park until a manual `restart_backend` clears the flag rather
+                // than tearing down the only supervisor thread.
+                state.shutting_down.store(true, Ordering::SeqCst);
+                continue;
+            }
+
+            let backoff = restart_backoff(restarts);
+            restarts += 1;
+            eprintln!(
+                "Backend exited unexpectedly; restart {}/{} in {:?}.",
+                restarts, MAX_RESTARTS, backoff
+            );
+            let _ = app.emit("backend-restarting", restarts);
+            std::thread::sleep(backoff);
+
+            // Respawn under the lock, but only if the slot is still empty. A
+            // manual `restart_backend` during the backoff window may have
+            // installed a live child; overwriting it would orphan that process
+            // (`Child`'s drop does not kill) and re-leak the port.
+            let mut guard = state.child.lock().unwrap();
+            if state.shutting_down.load(Ordering::SeqCst) {
+                continue;
+            }
+            if guard.is_some() {
+                restarts = 0;
+                continue;
+            }
+            let respawned = spawn_backend(state.port);
+            if respawned.is_some() {
+                *state.started_at.lock().unwrap() = Some(Instant::now());
+            }
+            *guard = respawned;
+            drop(guard);
+            probe_backend_ready(app.clone());
+        }
+    });
+}
+
+/// Exponential backoff (1s, 2s, 4s, …) capped at `MAX_BACKOFF`.
+fn restart_backoff(attempt: u32) -> Duration {
+    let secs = 1u64.checked_shl(attempt).unwrap_or(u64::MAX);
+    Duration::from_secs(secs).min(MAX_BACKOFF)
+}
+
+/// Ask the backend to stop gracefully so uvicorn can run its shutdown handlers
+/// and release its port, only hard-killing if it outstays `GRACEFUL_TIMEOUT`.
+/// On Windows there is no graceful step: the child is hard-killed at once.
+fn terminate_backend(child: &mut Child) {
+    #[cfg(unix)]
+    unsafe {
+        // SIGTERM is what uvicorn treats as a clean shutdown request.
+        libc::kill(child.id() as libc::pid_t, libc::SIGTERM);
+    }
+    #[cfg(windows)]
+    {
+        // No CTRL_BREAK path: the child isn't spawned in its own console
+        // process group (CREATE_NEW_PROCESS_GROUP), so GenerateConsoleCtrlEvent
+        // can't target it. Windows shutdown is therefore hard-kill only —
+        // uvicorn does not get to run its shutdown handlers here.
+        let _ = child.kill();
+    }
+
+    let deadline = Instant::now() + GRACEFUL_TIMEOUT;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => {
+                println!("Backend process terminated.");
+                return;
+            }
+            // Already reaped elsewhere — nothing left to wait for, so don't
+            // stall the synchronous exit path for the full graceful timeout.
+            Err(_) => {
+                println!("Backend process already exited.");
+                return;
+            }
+            Ok(None) => {}
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            println!("Backend process hard-killed after graceful timeout.");
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// Flag the backend as shutting down and terminate it. Safe to call from any
+/// exit route; a no-op once the child has already been taken.
+fn shutdown_backend(state: &BackendProcess) {
+    state.shutting_down.store(true, Ordering::SeqCst);
+    if let Some(mut child) = state.child.lock().unwrap().take() {
+        terminate_backend(&mut child);
+    }
+}
+
+/// Custom URI scheme used by the webview to reach the backend same-origin.
+const API_SCHEME: &str = "lazylearn";
+
+/// Headers that describe the transport hop, not the payload, and must not be
+/// forwarded when we re-frame the response with a buffered body.
+fn is_hop_by_hop(name: &str) -> bool {
+    matches!(
+        name.to_ascii_lowercase().as_str(),
+        "transfer-encoding" | "content-length" | "connection" | "keep-alive"
+    )
+}
+
+/// Bridge a `lazylearn://api/...` request into an HTTP call against the managed
+/// backend and hand the response straight back to the webview. Keeping the
+/// window-to-backend channel same-origin removes CORS configuration, hides the
+/// ephemeral port, and centralizes error handling for the bridge in Rust.
+///
+/// The blocking `reqwest` call runs on a worker thread and replies through the
+/// `UriSchemeResponder`, so a slow or hung backend can't block the webview's
+/// resource load (or the main thread).
+fn handle_api_protocol(app: &AppHandle, request: Request<Vec<u8>>, responder: UriSchemeResponder) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        responder.respond(proxy_request(&app, request));
+    });
+}
+
+/// Synchronously forward one request to the backend and buffer its response.
+/// Runs off the main thread (see `handle_api_protocol`).
+fn proxy_request(app: &AppHandle, request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let base_url = app.state::<BackendProcess>().base_url.clone();
+    let uri = request.uri();
+    // `lazylearn://api/<path>?<query>` -> `<base_url>/<path>?<query>`.
+    let url = match uri.query() {
+        Some(query) => format!("{}{}?{}", base_url, uri.path(), query),
+        None => format!("{}{}", base_url, uri.path()),
+    };
+
+    let method = reqwest::Method::from_bytes(request.method().as_str().as_bytes())
+        .unwrap_or(reqwest::Method::GET);
+    let client = reqwest::blocking::Client::new();
+    let mut outgoing = client.request(method, &url);
+    for (name, value) in request.headers() {
+        // Drop transport/framing headers: reqwest re-frames the buffered body
+        // and sets its own `host`, so forwarding the inbound ones (including the
+        // custom-scheme `host`) would mis-frame the request.
+        if is_hop_by_hop(name.as_str()) || name.as_str().eq_ignore_ascii_case("host") {
+            continue;
+        }
+        outgoing = outgoing.header(name.as_str(), value.as_bytes());
+    }
+    outgoing = outgoing.body(request.body().clone());
+
+    match outgoing.send() {
+        Ok(resp) => {
+            let status = resp.status().as_u16();
+            let headers = resp.headers().clone();
+            let body = resp.bytes().map(|b| b.to_vec()).unwrap_or_default();
+            let mut builder = Response::builder().status(status);
+            for (name, value) in headers.iter() {
+                // Skip hop-by-hop headers: we buffer the body into a
+                // fixed-length `Vec<u8>`, so a forwarded `transfer-encoding` or
+                // stale `content-length` would corrupt the response. The body is
+                // handed back whole, with no length header copied from upstream.
+                if is_hop_by_hop(name.as_str()) {
+                    continue;
+                }
+                builder = builder.header(name.as_str(), value.as_bytes());
+            }
+            builder.body(body).unwrap_or_else(|_| Response::new(Vec::new()))
+        }
+        Err(e) => Response::builder()
+            .status(502)
+            .body(format!("backend proxy error: {}", e).into_bytes())
+            .expect("failed to build proxy error response"),
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
-        .manage(BackendProcess(Mutex::new(None)))
+        .register_asynchronous_uri_scheme_protocol(API_SCHEME, handle_api_protocol)
+        .manage(BackendProcess::new(free_port()))
         .setup(|app| {
-            // Spawn backend on startup
-            let backend = spawn_backend();
+            // Spawn backend on startup, on the port chosen for this run.
             let state: State<BackendProcess> = app.state();
-            *state.0.lock().unwrap() = backend;
+            let backend = spawn_backend(state.port);
+            if backend.is_some() {
+                *state.started_at.lock().unwrap() = Some(Instant::now());
+            }
+            *state.child.lock().unwrap() = backend;
+            // Let the frontend know when the API is actually reachable.
+            probe_backend_ready(app.handle().clone());
+            // Keep the backend alive for the rest of the session.
+            supervise_backend(app.handle().clone());
             Ok(())
         })
-        .on_window_event(|window, event| {
-            if let tauri::WindowEvent::CloseRequested { .. } = event {
-                // Kill backend when window closes
-                let state: State<BackendProcess> = window.state();
-                if let Some(mut child) = state.0.lock().unwrap().take() {
-                    let _ = child.kill();
-                    println!("Backend process terminated.");
-                }
+        // No per-window handler: flagging shutdown on any CloseRequested would
+        // park the supervisor for good when a *secondary* window closes while
+        // the app keeps running. Teardown is driven entirely by the RunEvent
+        // hooks below, which only fire when the app is actually exiting.
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            backend_url,
+            restart_backend,
+            stop_backend,
+            get_backend_status
+        ])
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Cover every shutdown route (tray quit, AppHandle::exit, a crash of
+            // the main window, …) — not just CloseRequested on one window.
+            if let RunEvent::ExitRequested { .. } | RunEvent::Exit = event {
+                shutdown_backend(&app.state::<BackendProcess>());
             }
-        })
-        .invoke_handler(tauri::generate_handler![greet])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        });
 }